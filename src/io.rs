@@ -0,0 +1,153 @@
+//! Helpers for inspecting byte streams before decoding them.
+
+use std::io::{self, BufRead};
+
+/// A text encoding that [`sniff`] can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+/// The result of [`sniff`]: the likely encoding of a stream and whether it
+/// begins with a byte-order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodingGuess {
+    pub encoding: Encoding,
+    pub bom: bool,
+}
+
+impl EncodingGuess {
+    /// Number of leading bytes taken up by the byte-order mark, which a
+    /// decoder should skip.
+    pub fn bom_len(&self) -> usize {
+        match (self.bom, self.encoding) {
+            (false, _) | (true, Encoding::Latin1) => 0,
+            (true, Encoding::Utf8) => 3,
+            (true, Encoding::Utf16Le | Encoding::Utf16Be) => 2,
+        }
+    }
+}
+
+/// Guesses the encoding of `reader` from the bytes it currently has buffered.
+///
+/// Nothing is consumed, so the same reader can be handed on to a decoder
+/// afterwards. A byte-order mark decides the answer outright. Otherwise the
+/// buffer is checked for the zero-byte pattern typical of UTF-16 text, then
+/// for valid UTF-8, and Latin-1 is reported when neither fits, since every
+/// byte sequence is valid Latin-1.
+///
+/// ```
+/// use medley::io::{sniff, Encoding};
+///
+/// let mut input: &[u8] = b"\xEF\xBB\xBFhello";
+/// let guess = sniff(&mut input).unwrap();
+/// assert_eq!(guess.encoding, Encoding::Utf8);
+/// assert_eq!(guess.bom_len(), 3);
+/// assert_eq!(input.len(), 8);
+/// ```
+pub fn sniff<R: BufRead + ?Sized>(reader: &mut R) -> io::Result<EncodingGuess> {
+    Ok(guess(reader.fill_buf()?))
+}
+
+fn guess(bytes: &[u8]) -> EncodingGuess {
+    let with_bom = |encoding| EncodingGuess {
+        encoding,
+        bom: true,
+    };
+    let without_bom = |encoding| EncodingGuess {
+        encoding,
+        bom: false,
+    };
+
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return with_bom(Encoding::Utf8);
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return with_bom(Encoding::Utf16Le);
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return with_bom(Encoding::Utf16Be);
+    }
+
+    // Mostly-ASCII UTF-16 text has a zero in every other byte; which half the
+    // zeros fall in gives away the byte order.
+    let pairs = bytes.len() / 2;
+    if pairs > 0 {
+        let (mut even, mut odd) = (0, 0);
+        for pair in bytes.chunks_exact(2) {
+            even += usize::from(pair[0] == 0);
+            odd += usize::from(pair[1] == 0);
+        }
+        if odd * 2 > pairs && even * 8 < pairs {
+            return without_bom(Encoding::Utf16Le);
+        }
+        if even * 2 > pairs && odd * 8 < pairs {
+            return without_bom(Encoding::Utf16Be);
+        }
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(_) => without_bom(Encoding::Utf8),
+        // The buffer may end partway through a multi-byte sequence.
+        Err(e) if e.error_len().is_none() => without_bom(Encoding::Utf8),
+        Err(_) => without_bom(Encoding::Latin1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sniff_bytes(mut bytes: &[u8]) -> EncodingGuess {
+        sniff(&mut bytes).unwrap()
+    }
+
+    #[test]
+    fn detects_boms() {
+        let guess = sniff_bytes(b"\xEF\xBB\xBFabc");
+        assert_eq!((guess.encoding, guess.bom_len()), (Encoding::Utf8, 3));
+        let guess = sniff_bytes(b"\xFF\xFEa\0");
+        assert_eq!((guess.encoding, guess.bom_len()), (Encoding::Utf16Le, 2));
+        let guess = sniff_bytes(b"\xFE\xFF\0a");
+        assert_eq!((guess.encoding, guess.bom_len()), (Encoding::Utf16Be, 2));
+    }
+
+    #[test]
+    fn detects_utf16_without_bom() {
+        assert_eq!(sniff_bytes(b"h\0e\0l\0l\0o\0").encoding, Encoding::Utf16Le);
+        assert_eq!(sniff_bytes(b"\0h\0e\0l\0l\0o").encoding, Encoding::Utf16Be);
+    }
+
+    #[test]
+    fn plain_text_is_utf8() {
+        let guess = sniff_bytes("grüße".as_bytes());
+        assert_eq!(
+            guess,
+            EncodingGuess {
+                encoding: Encoding::Utf8,
+                bom: false
+            }
+        );
+        assert_eq!(sniff_bytes(b"").encoding, Encoding::Utf8);
+    }
+
+    #[test]
+    fn truncated_utf8_sequence_is_still_utf8() {
+        assert_eq!(sniff_bytes(b"gr\xC3").encoding, Encoding::Utf8);
+    }
+
+    #[test]
+    fn invalid_utf8_falls_back_to_latin1() {
+        assert_eq!(sniff_bytes(b"gr\xFC\xDFe").encoding, Encoding::Latin1);
+    }
+
+    #[test]
+    fn does_not_consume_input() {
+        let mut input: &[u8] = b"abc";
+        sniff(&mut input).unwrap();
+        assert_eq!(input, b"abc");
+    }
+}
@@ -0,0 +1,111 @@
+//! Helpers for presenting regions of source text.
+
+use std::ops::Range;
+
+/// Renders the lines of `source` touched by the byte range `span`, each
+/// followed by a line of `^` markers under the covered characters.
+///
+/// The span is clamped to `source` and widened to the nearest character
+/// boundaries. An empty span is shown as a single `^` at its position. Tabs
+/// before the marked region are copied into the marker line so the carets
+/// stay aligned however the output is displayed.
+///
+/// ```
+/// let out = medley::text::underline("let x = 1;", 4..5);
+/// assert_eq!(out, "let x = 1;\n    ^");
+/// ```
+pub fn underline(source: &str, span: Range<usize>) -> String {
+    let mut start = span.start.min(source.len());
+    let mut end = span.end.clamp(start, source.len());
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !source.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let mut out = String::new();
+    let mut line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    loop {
+        let line_end = source[line_start..]
+            .find('\n')
+            .map_or(source.len(), |i| line_start + i);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+
+        let from = start.max(line_start) - line_start;
+        let to = end.min(line_end) - line_start;
+
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(line);
+        out.push('\n');
+        for c in line.get(..from).unwrap_or(line).chars() {
+            out.push(if c == '\t' { '\t' } else { ' ' });
+        }
+        let width = line
+            .get(from..to.min(line.len()))
+            .map_or(0, |s| s.chars().count());
+        for _ in 0..width.max(1) {
+            out.push('^');
+        }
+
+        if end <= line_end || line_end == source.len() {
+            break;
+        }
+        line_start = line_end + 1;
+        if line_start == end {
+            break;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_line() {
+        assert_eq!(underline("abc def", 4..7), "abc def\n    ^^^");
+    }
+
+    #[test]
+    fn picks_the_line_containing_the_span() {
+        let src = "first\nsecond\nthird";
+        assert_eq!(underline(src, 7..10), "second\n ^^^");
+    }
+
+    #[test]
+    fn empty_span_gets_one_caret() {
+        assert_eq!(underline("abc", 3..3), "abc\n   ^");
+        assert_eq!(underline("", 0..0), "\n^");
+    }
+
+    #[test]
+    fn multi_line_span() {
+        let src = "one\ntwo\nthree";
+        assert_eq!(underline(src, 1..10), "one\n ^^\ntwo\n^^^\nthree\n^^");
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(underline("héllo wörld", 7..13), "héllo wörld\n      ^^^^^");
+    }
+
+    #[test]
+    fn keeps_tabs_for_alignment() {
+        assert_eq!(underline("\tx = 1", 1..2), "\tx = 1\n\t^");
+    }
+
+    #[test]
+    fn clamps_out_of_range_and_mid_char_spans() {
+        assert_eq!(underline("abc", 1..99), "abc\n ^^");
+        assert_eq!(underline("é", 1..1), "é\n^");
+    }
+
+    #[test]
+    fn ignores_carriage_returns() {
+        assert_eq!(underline("ab\r\ncd", 0..2), "ab\n^^");
+    }
+}